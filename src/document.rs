@@ -3,11 +3,77 @@ use std::cmp::Ordering;
 use std::fmt;
 use std::fs;
 use std::io::{Error, Write};
-use std::slice::Iter;
+
+/// Which buffer a `Piece` draws its bytes from.
+#[derive(Clone, Copy)]
+enum Source {
+    Original,
+    Add,
+}
+
+/// A span of bytes in either the `original` or `add` buffer. A row's text
+/// is the concatenation of its pieces, in order.
+#[derive(Clone, Copy)]
+struct Piece {
+    source: Source,
+    start: usize,
+    len: usize,
+}
+
+/// The line terminator a document was opened with. Applied as a row
+/// separator by `save`; rows never store their own terminator.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+
+    /// Short label suitable for the status bar, e.g. `"LF"` or `"CRLF"`.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Lf => "LF",
+            Self::CrLf => "CRLF",
+        }
+    }
+}
 
 pub struct Document {
-    rows: Vec<Row>,
+    /// Contents as they were when opened. Never mutated.
+    original: Vec<u8>,
+    /// Append-only buffer holding every byte typed into the document.
+    add: Vec<u8>,
+    /// Each row's pieces, in order. Edits to one row never touch another
+    /// row's pieces.
+    rows: Vec<Vec<Piece>>,
     pub filename: String,
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    /// Position the most recent edit left the cursor at.
+    last_cursor: Position,
+    /// Set while a run of single-char inserts is being coalesced into one
+    /// undo step.
+    insert_group: Option<(usize, usize)>,
+    /// Line terminator `save` reproduces instead of always writing `\n`.
+    line_ending: LineEnding,
+    /// Whether the file ended with a line terminator after its last row.
+    final_newline: bool,
+}
+
+/// A point the buffer can be rewound or forwarded to.
+struct Snapshot {
+    rows: Vec<Vec<Piece>>,
+    cursor: Position,
 }
 
 impl fmt::Debug for Document {
@@ -19,8 +85,16 @@ impl fmt::Debug for Document {
 impl Default for Document {
     fn default() -> Self {
         Self {
-            rows: vec![Row::from("")],
+            original: Vec::new(),
+            add: Vec::new(),
+            rows: vec![Vec::new()],
             filename: "".to_string(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            last_cursor: Position::top_left(),
+            insert_group: None,
+            line_ending: LineEnding::default(),
+            final_newline: false,
         }
     }
 }
@@ -28,49 +102,136 @@ impl Default for Document {
 impl Document {
     #[must_use]
     pub fn new(rows: Vec<Row>, filename: String) -> Self {
-        Self { rows, filename }
+        let mut document = Self {
+            original: Vec::new(),
+            add: Vec::new(),
+            rows: Vec::new(),
+            filename,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            last_cursor: Position::top_left(),
+            insert_group: None,
+            line_ending: LineEnding::default(),
+            final_newline: false,
+        };
+        for row in &rows {
+            let pieces = document.pieces_for_new_text(row.string.as_str());
+            document.rows.push(pieces);
+        }
+        document
     }
 
     #[must_use]
     pub fn new_empty(filename: String) -> Self {
         Self {
-            rows: vec![Row::from("")],
+            original: Vec::new(),
+            add: Vec::new(),
+            rows: vec![Vec::new()],
             filename,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            last_cursor: Position::top_left(),
+            insert_group: None,
+            line_ending: LineEnding::default(),
+            final_newline: false,
         }
     }
+
     /// # Errors
     ///
     /// Returns an error if a file bearing the provided filename
     /// cannot be open.
     pub fn open(filename: &str) -> Result<Self, Error> {
         let file_contents = fs::read_to_string(filename)?;
+        let original = file_contents.clone().into_bytes();
+        let line_ending = if file_contents.contains("\r\n") {
+            LineEnding::CrLf
+        } else {
+            LineEnding::default()
+        };
+        let final_newline = file_contents.ends_with('\n');
+
         let mut rows = Vec::new();
-        for line in file_contents.lines() {
-            rows.push(Row::from(line));
+        let mut offset = 0usize;
+        let raw_lines: Vec<&str> = file_contents.split('\n').collect();
+        let last = raw_lines.len().saturating_sub(1);
+        for (i, raw_line) in raw_lines.iter().enumerate() {
+            // str::lines() doesn't yield a trailing empty row for a final '\n'
+            if i == last && raw_line.is_empty() {
+                break;
+            }
+            let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+            let pieces = if line.is_empty() {
+                Vec::new()
+            } else {
+                vec![Piece {
+                    source: Source::Original,
+                    start: offset,
+                    len: line.len(),
+                }]
+            };
+            rows.push(pieces);
+            offset += raw_line.len() + 1; // + 1 for the '\n' consumed by split
         }
+
         Ok(Self {
+            original,
+            add: Vec::new(),
             rows,
             filename: filename.to_string().clone(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            last_cursor: Position::top_left(),
+            insert_group: None,
+            line_ending,
+            final_newline,
         })
     }
 
     /// # Errors
     ///
     /// Can return an error if the file can't be created or written to.
-    pub fn save(&self) -> Result<(), Error> {
+    pub fn save(&mut self) -> Result<(), Error> {
+        self.break_undo_group();
         if !self.filename.is_empty() {
             let mut file = fs::File::create(self.filename.as_str())?;
-            for row in &self.rows {
-                file.write_all(row.as_bytes())?;
-                file.write_all(b"\n")?;
+            let separator = self.line_ending.as_str().as_bytes();
+            let last = self.rows.len().saturating_sub(1);
+            for (y, pieces) in self.rows.iter().enumerate() {
+                for piece in pieces {
+                    file.write_all(self.piece_bytes(piece))?;
+                }
+                if y != last || self.final_newline {
+                    file.write_all(separator)?;
+                }
             }
         }
         Ok(())
     }
 
+    /// The line terminator detected when this document was opened.
+    #[must_use]
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Whether the file had a trailing line terminator after its last row.
+    #[must_use]
+    pub fn has_final_newline(&self) -> bool {
+        self.final_newline
+    }
+
+    /// Changes the line terminator `save` will write from now on, without
+    /// touching the buffer itself.
+    pub fn set_line_ending(&mut self, line_ending: LineEnding) {
+        self.line_ending = line_ending;
+    }
+
+    /// Materializes row `index` by walking its pieces.
     #[must_use]
-    pub fn get_row(&self, index: usize) -> Option<&Row> {
-        self.rows.get(index)
+    pub fn get_row(&self, index: usize) -> Option<Row> {
+        let pieces = self.rows.get(index)?;
+        Some(Row::from(self.row_text(pieces).as_str()))
     }
 
     #[must_use]
@@ -85,12 +246,12 @@ impl Document {
 
     #[must_use]
     pub fn num_words(&self) -> usize {
-        self.iter().map(Row::num_words).sum()
+        self.iter().map(|row| row.num_words()).sum()
     }
 
     /// Get the document row corresponding to a given line number
     #[must_use]
-    pub fn row_for_line_number(&self, line_number: usize) -> Option<&Row> {
+    pub fn row_for_line_number(&self, line_number: usize) -> Option<Row> {
         self.get_row(line_number.saturating_sub(1))
     }
 
@@ -100,38 +261,59 @@ impl Document {
         self.num_rows()
     }
 
-    #[must_use]
-    pub fn iter(&self) -> Iter<Row> {
-        self.rows.iter()
+    pub fn iter(&self) -> impl Iterator<Item = Row> + '_ {
+        self.rows
+            .iter()
+            .map(move |pieces| Row::from(self.row_text(pieces).as_str()))
     }
 
     pub fn insert(&mut self, c: char, x: usize, y: usize) {
+        let continues_group = c != '\n' && self.insert_group == Some((x, y));
+        if continues_group {
+            self.redo_stack.clear();
+        } else {
+            self.push_undo(Position { x, y, x_offset: 0 });
+        }
+        self.insert_group = Some((x.saturating_add(1), y));
+
         match y.cmp(&self.num_rows()) {
             Ordering::Equal | Ordering::Greater => {
-                let mut row = Row::default();
-                row.insert(0, c);
-                self.rows.push(row);
+                let piece = self.new_char_piece(c);
+                self.rows.push(vec![piece]);
             }
             Ordering::Less => {
-                if let Some(row) = self.rows.get_mut(y) {
-                    row.insert(x, c);
-                }
+                self.insert_char_piece(y, x, c);
             }
         }
+        self.last_cursor = Position {
+            x: x.saturating_add(1),
+            y,
+            x_offset: 0,
+        };
     }
 
     pub fn delete(&mut self, x: usize, y: usize) {
         if y >= self.num_rows() {
             return;
         }
+        self.push_undo(Position { x, y, x_offset: 0 });
         // Deletion at the very start of a line means we append the current line to the previous one
         if x == 0 && y > 0 {
             let current_row = self.rows.remove(y);
-            if let Some(previous_row) = self.rows.get_mut(y - 1) {
-                previous_row.append(&current_row);
-            }
-        } else if let Some(row) = self.rows.get_mut(y) {
-            row.delete(x.saturating_sub(1));
+            let previous_len = self.row_char_len(y - 1);
+            self.rows[y - 1].extend(current_row);
+            self.last_cursor = Position {
+                x: previous_len,
+                y: y - 1,
+                x_offset: 0,
+            };
+        } else {
+            self.delete_char_piece(y, x.saturating_sub(1));
+            self.last_cursor = Position {
+                x: x.saturating_sub(1),
+                y,
+                x_offset: 0,
+            };
         }
     }
 
@@ -139,32 +321,285 @@ impl Document {
         if y > self.num_rows() {
             return;
         }
-        let current_row = self.rows.get_mut(y);
-        if let Some(current_row) = current_row {
-            if x < current_row.len().saturating_sub(1) {
-                let split_row = current_row.split(x);
-                self.rows.insert(y.saturating_add(1), split_row)
+        self.push_undo(Position { x, y, x_offset: 0 });
+        if self.rows.get(y).is_some() {
+            let row_len = self.row_char_len(y);
+            if x < row_len.saturating_sub(1) {
+                let (left, right) = self.split_row_pieces(y, x);
+                self.rows[y] = left;
+                self.rows.insert(y.saturating_add(1), right);
                 // newline inserted in the middle of the row
+            } else if y == self.num_rows() || y.saturating_add(1) == self.num_rows() {
+                self.rows.push(Vec::new());
             } else {
-                let new_row = Row::default();
-                if y == self.num_rows() || y.saturating_add(1) == self.num_rows() {
-                    self.rows.push(new_row);
-                } else {
-                    self.rows.insert(y.saturating_add(1), new_row)
-                }
+                self.rows.insert(y.saturating_add(1), Vec::new());
             }
         }
+        self.last_cursor = Position {
+            x: 0,
+            y: y.saturating_add(1),
+            x_offset: 0,
+        };
     }
 
     pub fn delete_row(&mut self, at: &Position) {
         if at.y > self.num_rows() {
-        } else if self.num_rows() == 1 {
-            if let Some(row) = self.rows.get_mut(0) {
-                row.string = "".to_string();
-            }
+            return;
+        }
+        self.push_undo(copy_position(at));
+        if self.num_rows() == 1 {
+            self.rows[0] = Vec::new();
         } else if self.rows.get(at.y).is_some() {
             self.rows.remove(at.y);
         }
+        self.last_cursor = copy_position(at);
+    }
+
+    /// Reverts the most recent edit (a run of coalesced inserts counts as
+    /// one), returning the position the cursor should go to.
+    #[must_use]
+    pub fn undo(&mut self) -> Option<Position> {
+        let snapshot = self.undo_stack.pop()?;
+        self.redo_stack.push(Snapshot {
+            rows: self.rows.clone(),
+            cursor: copy_position(&self.last_cursor),
+        });
+        let cursor = snapshot.cursor;
+        self.restore(snapshot);
+        self.break_undo_group();
+        Some(cursor)
+    }
+
+    /// Re-applies the most recently undone edit, returning the position the
+    /// cursor should go to.
+    #[must_use]
+    pub fn redo(&mut self) -> Option<Position> {
+        let snapshot = self.redo_stack.pop()?;
+        self.undo_stack.push(Snapshot {
+            rows: self.rows.clone(),
+            cursor: copy_position(&self.last_cursor),
+        });
+        let cursor = snapshot.cursor;
+        self.restore(snapshot);
+        self.break_undo_group();
+        Some(cursor)
+    }
+
+    fn push_undo(&mut self, cursor_before: Position) {
+        self.undo_stack.push(Snapshot {
+            rows: self.rows.clone(),
+            cursor: cursor_before,
+        });
+        self.redo_stack.clear();
+        self.insert_group = None;
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.rows = snapshot.rows;
+        self.last_cursor = snapshot.cursor;
+    }
+
+    /// Ends the current run of coalesced single-char inserts. Call on a bare
+    /// cursor move, since `Document` has no other way to observe one.
+    pub fn break_undo_group(&mut self) {
+        self.insert_group = None;
+    }
+
+    fn piece_bytes(&self, piece: &Piece) -> &[u8] {
+        match piece.source {
+            Source::Original => &self.original[piece.start..piece.start + piece.len],
+            Source::Add => &self.add[piece.start..piece.start + piece.len],
+        }
+    }
+
+    fn row_text(&self, pieces: &[Piece]) -> String {
+        let mut text = String::new();
+        for piece in pieces {
+            text.push_str(std::str::from_utf8(self.piece_bytes(piece)).unwrap_or(""));
+        }
+        text
+    }
+
+    fn row_char_len(&self, y: usize) -> usize {
+        self.rows.get(y).map_or(0, |pieces| {
+            pieces
+                .iter()
+                .map(|piece| {
+                    std::str::from_utf8(self.piece_bytes(piece))
+                        .unwrap_or("")
+                        .chars()
+                        .count()
+                })
+                .sum()
+        })
+    }
+
+    /// Copies `text` into the append-only buffer and returns a piece list
+    /// for it (empty if `text` is empty).
+    fn pieces_for_new_text(&mut self, text: &str) -> Vec<Piece> {
+        if text.is_empty() {
+            Vec::new()
+        } else {
+            let start = self.add.len();
+            self.add.extend_from_slice(text.as_bytes());
+            vec![Piece {
+                source: Source::Add,
+                start,
+                len: text.len(),
+            }]
+        }
+    }
+
+    fn new_char_piece(&mut self, c: char) -> Piece {
+        let mut buf = [0u8; 4];
+        let start = self.add.len();
+        self.add.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+        Piece {
+            source: Source::Add,
+            start,
+            len: c.len_utf8(),
+        }
+    }
+
+    /// Locates the piece and in-piece byte offset for char index `x` within
+    /// row `y`, as the row's pieces stood before the current edit.
+    fn locate_char(&self, y: usize, x: usize) -> Option<(usize, usize)> {
+        let pieces = self.rows.get(y)?;
+        let mut consumed_chars = 0usize;
+        for (i, piece) in pieces.iter().enumerate() {
+            let text = std::str::from_utf8(self.piece_bytes(piece)).unwrap_or("");
+            let char_count = text.chars().count();
+            if consumed_chars + char_count >= x {
+                let local = x - consumed_chars;
+                let byte_offset = text
+                    .char_indices()
+                    .nth(local)
+                    .map_or(text.len(), |(b, _)| b);
+                return Some((i, byte_offset));
+            }
+            consumed_chars += char_count;
+        }
+        None
+    }
+
+    /// Splits row `y`'s pieces at char `x`, returning the pieces that
+    /// precede the split and the pieces that follow it.
+    fn split_row_pieces(&self, y: usize, x: usize) -> (Vec<Piece>, Vec<Piece>) {
+        let Some(pieces) = self.rows.get(y) else {
+            return (Vec::new(), Vec::new());
+        };
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        let mut consumed = 0usize;
+        let mut split_done = false;
+        for piece in pieces {
+            if split_done {
+                right.push(*piece);
+                continue;
+            }
+            let text = std::str::from_utf8(self.piece_bytes(piece)).unwrap_or("");
+            let char_count = text.chars().count();
+            if consumed + char_count <= x {
+                left.push(*piece);
+                consumed += char_count;
+                continue;
+            }
+            let local = x - consumed;
+            let byte_offset = text
+                .char_indices()
+                .nth(local)
+                .map_or(text.len(), |(b, _)| b);
+            if byte_offset > 0 {
+                left.push(Piece {
+                    source: piece.source,
+                    start: piece.start,
+                    len: byte_offset,
+                });
+            }
+            if byte_offset < piece.len {
+                right.push(Piece {
+                    source: piece.source,
+                    start: piece.start + byte_offset,
+                    len: piece.len - byte_offset,
+                });
+            }
+            split_done = true;
+        }
+        (left, right)
+    }
+
+    /// Splits the piece covering char `x` of row `y` at the cursor and
+    /// inserts a new piece for `c`, giving the row up to three pieces where
+    /// it had one. Only row `y`'s own piece list is touched.
+    fn insert_char_piece(&mut self, y: usize, x: usize, c: char) {
+        let new_piece = self.new_char_piece(c);
+        match self.locate_char(y, x) {
+            Some((i, byte_offset)) => {
+                let piece = self.rows[y][i];
+                let mut replacement = Vec::with_capacity(3);
+                if byte_offset > 0 {
+                    replacement.push(Piece {
+                        source: piece.source,
+                        start: piece.start,
+                        len: byte_offset,
+                    });
+                }
+                replacement.push(new_piece);
+                if byte_offset < piece.len {
+                    replacement.push(Piece {
+                        source: piece.source,
+                        start: piece.start + byte_offset,
+                        len: piece.len - byte_offset,
+                    });
+                }
+                self.rows[y].splice(i..=i, replacement);
+            }
+            None => self.rows[y].push(new_piece),
+        }
+    }
+
+    /// Trims (or splits, if the deleted char sits in the middle of a piece)
+    /// the piece covering char `x` of row `y`. Only row `y`'s own piece
+    /// list is touched.
+    fn delete_char_piece(&mut self, y: usize, x: usize) {
+        let Some((i, byte_offset)) = self.locate_char(y, x) else {
+            return;
+        };
+        let piece = self.rows[y][i];
+        let text = std::str::from_utf8(self.piece_bytes(&piece)).unwrap_or("");
+        let removed_len = text[byte_offset..]
+            .chars()
+            .next()
+            .map_or(0, char::len_utf8);
+        if removed_len == 0 {
+            return;
+        }
+
+        let mut replacement = Vec::with_capacity(2);
+        if byte_offset > 0 {
+            replacement.push(Piece {
+                source: piece.source,
+                start: piece.start,
+                len: byte_offset,
+            });
+        }
+        let after_start = byte_offset + removed_len;
+        if after_start < piece.len {
+            replacement.push(Piece {
+                source: piece.source,
+                start: piece.start + after_start,
+                len: piece.len - after_start,
+            });
+        }
+        self.rows[y].splice(i..=i, replacement);
+    }
+}
+
+fn copy_position(position: &Position) -> Position {
+    Position {
+        x: position.x,
+        y: position.y,
+        x_offset: position.x_offset,
     }
 }
 
@@ -0,0 +1,128 @@
+use super::*;
+
+fn temp_path(tag: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("document_test_{tag}_{}.txt", std::process::id()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn inserts_into_new_empty_document_are_not_lost_on_save() {
+    let path = temp_path("new_row");
+    let mut document = Document::new_empty(path.clone());
+    document.insert('a', 0, 0);
+    document.insert('b', 1, 0);
+    document.save().unwrap();
+
+    let saved = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).ok();
+    assert_eq!(saved, "ab");
+}
+
+#[test]
+fn default_document_accepts_its_first_insert() {
+    let mut document = Document::default();
+    document.insert('x', 0, 0);
+    assert_eq!(document.get_row(0).unwrap().string, "x");
+}
+
+#[test]
+fn new_empty_document_accepts_its_first_insert() {
+    let mut document = Document::new_empty(String::new());
+    document.insert('y', 0, 0);
+    assert_eq!(document.get_row(0).unwrap().string, "y");
+}
+
+#[test]
+fn consecutive_inserts_coalesce_into_one_undo_step() {
+    let mut document = Document::new_empty(String::new());
+    document.insert('a', 0, 0);
+    document.insert('b', 1, 0);
+    assert_eq!(document.get_row(0).unwrap().string, "ab");
+
+    document.undo();
+    assert_eq!(document.get_row(0).unwrap().string, "");
+}
+
+#[test]
+fn break_undo_group_stops_coalescing() {
+    let mut document = Document::new_empty(String::new());
+    document.insert('a', 0, 0);
+    document.break_undo_group();
+    document.insert('b', 1, 0);
+    assert_eq!(document.get_row(0).unwrap().string, "ab");
+
+    document.undo();
+    assert_eq!(document.get_row(0).unwrap().string, "a");
+
+    document.undo();
+    assert_eq!(document.get_row(0).unwrap().string, "");
+}
+
+#[test]
+fn crlf_line_ending_round_trips_through_open_and_save() {
+    let path = temp_path("crlf");
+    fs::write(&path, "one\r\ntwo\r\n").unwrap();
+
+    let mut document = Document::open(&path).unwrap();
+    assert_eq!(document.line_ending(), LineEnding::CrLf);
+    assert!(document.has_final_newline());
+
+    document.save().unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).ok();
+    assert_eq!(saved, "one\r\ntwo\r\n");
+}
+
+#[test]
+fn missing_final_newline_is_preserved_on_save() {
+    let path = temp_path("no_trailing_newline");
+    fs::write(&path, "one\ntwo").unwrap();
+
+    let mut document = Document::open(&path).unwrap();
+    assert_eq!(document.line_ending(), LineEnding::Lf);
+    assert!(!document.has_final_newline());
+
+    document.save().unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).ok();
+    assert_eq!(saved, "one\ntwo");
+}
+
+#[test]
+fn insert_near_the_start_of_a_row_does_not_touch_other_rows() {
+    let rows = vec![Row::from("first"), Row::from("second"), Row::from("third")];
+    let mut document = Document::new(rows, String::new());
+    document.insert('X', 0, 1);
+    assert_eq!(document.get_row(0).unwrap().string, "first");
+    assert_eq!(document.get_row(1).unwrap().string, "Xsecond");
+    assert_eq!(document.get_row(2).unwrap().string, "third");
+}
+
+#[test]
+fn insert_newline_splits_a_row_in_the_middle() {
+    let mut document = Document::new_empty(String::new());
+    for c in "abcdef".chars() {
+        document.insert(c, document.get_row(0).unwrap().string.len(), 0);
+    }
+    document.insert_newline(3, 0);
+    assert_eq!(document.get_row(0).unwrap().string, "abc");
+    assert_eq!(document.get_row(1).unwrap().string, "def");
+}
+
+#[test]
+fn delete_merges_current_row_into_previous_row() {
+    let mut document = Document::new_empty(String::new());
+    for c in "ab".chars() {
+        document.insert(c, document.get_row(0).unwrap().string.len(), 0);
+    }
+    document.insert_newline(2, 0);
+    for c in "cd".chars() {
+        let len = document.get_row(1).unwrap().string.len();
+        document.insert(c, len, 1);
+    }
+    document.delete(0, 1);
+    assert_eq!(document.num_rows(), 1);
+    assert_eq!(document.get_row(0).unwrap().string, "abcd");
+}
@@ -1,9 +1,12 @@
 use crate::Position;
 use std::cmp;
 use std::fmt;
-use std::io::{self, stdout, Write};
+use std::io::{stdout, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
 use termion::color;
-use termion::event::{Event, MouseEvent};
+use termion::event::{Event as TermionEvent, MouseEvent};
 use termion::input::{MouseTerminal, TermRead};
 use termion::raw::{IntoRawMode, RawTerminal};
 use termion::screen::{AlternateScreen, ToAlternateScreen, ToMainScreen};
@@ -14,9 +17,74 @@ pub struct Size {
     pub width: u16,
 }
 
+/// Foreground/background pair a `Cell` is drawn with. `None` means no
+/// escape is emitted for that channel, not that it's reset.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Style {
+    pub fg: Option<color::Rgb>,
+    pub bg: Option<color::Rgb>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    ch: char,
+    style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            style: Style::default(),
+        }
+    }
+}
+
+/// A full screen's worth of cells, built with [`Frame::put_str`] and
+/// diffed against the previous frame by [`Terminal::present`].
+#[derive(Clone)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    fn blank(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width * height],
+        }
+    }
+
+    pub fn put_str(&mut self, x: usize, y: usize, text: &str, style: Style) {
+        if y >= self.height {
+            return;
+        }
+        for (i, ch) in text.chars().enumerate() {
+            let col = x + i;
+            if col >= self.width {
+                break;
+            }
+            self.cells[y * self.width + col] = Cell { ch, style };
+        }
+    }
+}
+
+/// An input event, widened to also carry terminal resizes.
+#[derive(Debug, Clone)]
+pub enum Event {
+    Input(TermionEvent),
+    Resize(u16, u16),
+}
+
 pub struct Terminal {
     size: Size,
     _stdout: AlternateScreen<MouseTerminal<RawTerminal<std::io::Stdout>>>,
+    next_frame: Frame,
+    presented: Option<Frame>,
+    events: Receiver<Event>,
 }
 
 impl fmt::Debug for Terminal {
@@ -34,20 +102,137 @@ impl Terminal {
     /// or if the stdout cannot be put into raw mode.
     pub fn default() -> Result<Self, std::io::Error> {
         let size = termion::terminal_size()?;
+        let height = size.1.saturating_sub(2); // to leave space for the status and message bars
+        let width = size.0;
         Ok(Self {
-            size: Size {
-                height: size.1.saturating_sub(2), // to leave space for the status and message bars
-                width: size.0,
-            },
+            size: Size { height, width },
             _stdout: AlternateScreen::from(MouseTerminal::from(stdout().into_raw_mode()?)),
+            next_frame: Frame::blank(width as usize, (height as usize).saturating_add(2)),
+            presented: None,
+            events: Self::spawn_event_thread(size),
         })
     }
 
+    /// Spawns the `terminal-event-buffer` thread, which polls stdin and the
+    /// terminal size and forwards decoded events over the returned channel.
+    fn spawn_event_thread(initial_size: (u16, u16)) -> Receiver<Event> {
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("terminal-event-buffer".to_string())
+            .spawn(move || {
+                let mut input = termion::async_stdin().events();
+                let mut last_size = initial_size;
+                loop {
+                    if let Ok(current_size) = termion::terminal_size() {
+                        if current_size != last_size {
+                            last_size = current_size;
+                            if sender
+                                .send(Event::Resize(current_size.0, current_size.1))
+                                .is_err()
+                            {
+                                return;
+                            }
+                        }
+                    }
+                    if let Some(Ok(event)) = input.next() {
+                        if sender.send(Event::Input(event)).is_err() {
+                            return;
+                        }
+                    }
+                    thread::sleep(Duration::from_millis(16));
+                }
+            })
+            .expect("failed to spawn terminal-event-buffer thread");
+        receiver
+    }
+
     #[must_use]
     pub fn size(&self) -> &Size {
         &self.size
     }
 
+    /// The frame callers should draw the next screen into. Cleared to
+    /// blanks after every [`Terminal::present`].
+    pub fn next_frame(&mut self) -> &mut Frame {
+        &mut self.next_frame
+    }
+
+    /// Diffs `next_frame` against the last presented frame and writes only
+    /// the runs of cells that changed, as a single flush. Swaps the frames
+    /// and clears `next_frame` for the following draw.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if stdout can't be written to or flushed.
+    pub fn present(&mut self) -> Result<(), std::io::Error> {
+        let width = self.next_frame.width;
+        let height = self.next_frame.height;
+        let mut out = String::new();
+
+        for y in 0..height {
+            let mut x = 0;
+            while x < width {
+                let idx = y * width + x;
+                let cell = self.next_frame.cells[idx];
+                let unchanged = self
+                    .presented
+                    .as_ref()
+                    .is_some_and(|prev| prev.cells[idx] == cell);
+                if unchanged {
+                    x += 1;
+                    continue;
+                }
+
+                let run_start = x;
+                let style = cell.style;
+                let mut text = String::new();
+                while x < width {
+                    let idx = y * width + x;
+                    let cell = self.next_frame.cells[idx];
+                    let unchanged = self
+                        .presented
+                        .as_ref()
+                        .is_some_and(|prev| prev.cells[idx] == cell);
+                    if unchanged || cell.style != style {
+                        break;
+                    }
+                    text.push(cell.ch);
+                    x += 1;
+                }
+
+                out.push_str(&format!(
+                    "{}",
+                    termion::cursor::Goto(run_start as u16 + 1, y as u16 + 1)
+                ));
+                if let Some(fg) = style.fg {
+                    out.push_str(&format!("{}", color::Fg(fg)));
+                }
+                if let Some(bg) = style.bg {
+                    out.push_str(&format!("{}", color::Bg(bg)));
+                }
+                out.push_str(&text);
+                if style.fg.is_some() {
+                    out.push_str(&format!("{}", color::Fg(color::Reset)));
+                }
+                if style.bg.is_some() {
+                    out.push_str(&format!("{}", color::Bg(color::Reset)));
+                }
+            }
+        }
+
+        print!("{out}");
+        Self::flush()?;
+        self.presented = Some(self.next_frame.clone());
+        self.next_frame = Frame::blank(width, height);
+        Ok(())
+    }
+
+    /// Discards the previously presented frame, forcing the next
+    /// `present` to repaint every cell. Call after a resize.
+    pub fn force_full_repaint(&mut self) {
+        self.presented = None;
+    }
+
     pub fn clear_screen() {
         print!("{}", termion::clear::All);
     }
@@ -63,16 +248,41 @@ impl Terminal {
         std::io::stdout().flush()
     }
 
+    /// Waits up to `timeout` for the next event (input or resize).
+    pub fn poll_event(&mut self, timeout: Duration) -> Option<Event> {
+        match self.events.recv_timeout(timeout) {
+            Ok(event) => {
+                self.apply_resize(&event);
+                Some(event)
+            }
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
     /// # Errors
     ///
-    /// Returns an error if a event can't be read
-    pub fn read_event() -> Result<Event, std::io::Error> {
-        loop {
-            let opt_key = io::stdin().lock().events().next();
-            // at that point, event is a Result<Event, Error>, as the Option was unwrapped
-            if let Some(event) = opt_key {
-                return event;
-            }
+    /// Returns an error if the `terminal-event-buffer` thread has shut down.
+    pub fn next_event(&mut self) -> Result<Event, std::io::Error> {
+        let event = self.events.recv().map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "terminal event thread stopped",
+            )
+        })?;
+        self.apply_resize(&event);
+        Ok(event)
+    }
+
+    /// On a `Resize` event, updates `self.size` and forces a full repaint on
+    /// the next `present` so stale layout doesn't linger on screen.
+    fn apply_resize(&mut self, event: &Event) {
+        if let Event::Resize(width, height) = *event {
+            self.size = Size {
+                height: height.saturating_sub(2),
+                width,
+            };
+            self.next_frame = Frame::blank(width as usize, height as usize);
+            self.force_full_repaint();
         }
     }
 
@@ -146,3 +356,7 @@ impl Terminal {
         print!("{}", termion::clear::All);
     }
 }
+
+#[cfg(test)]
+#[path = "./terminal_test.rs"]
+mod terminal_test;
@@ -0,0 +1,40 @@
+use super::*;
+
+#[test]
+fn blank_frame_is_all_default_cells() {
+    let frame = Frame::blank(3, 2);
+    assert_eq!(frame.cells.len(), 6);
+    assert!(frame.cells.iter().all(|cell| *cell == Cell::default()));
+}
+
+#[test]
+fn put_str_writes_styled_cells_at_the_given_row() {
+    let style = Style {
+        fg: Some(color::Rgb(1, 2, 3)),
+        bg: None,
+    };
+    let mut frame = Frame::blank(5, 2);
+    frame.put_str(1, 1, "hi", style);
+
+    let row_start = frame.width; // row 1 starts one full row (width 5) into cells
+    assert_eq!(frame.cells[row_start + 1], Cell { ch: 'h', style });
+    assert_eq!(frame.cells[row_start + 2], Cell { ch: 'i', style });
+    assert_eq!(frame.cells[row_start], Cell::default());
+}
+
+#[test]
+fn put_str_truncates_at_the_frame_edge() {
+    let mut frame = Frame::blank(3, 1);
+    frame.put_str(1, 0, "abcdef", Style::default());
+
+    assert_eq!(frame.cells[1].ch, 'a');
+    assert_eq!(frame.cells[2].ch, 'b');
+}
+
+#[test]
+fn put_str_ignores_rows_outside_the_frame() {
+    let mut frame = Frame::blank(3, 1);
+    frame.put_str(0, 5, "x", Style::default());
+
+    assert!(frame.cells.iter().all(|cell| *cell == Cell::default()));
+}